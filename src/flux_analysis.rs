@@ -1,23 +1,83 @@
 //! COBRA methods that take an LpProblem and a Solver
-use crate::model::ModelLP;
+use crate::model::ModelLp;
 use good_lp::{
-    solvers::ObjectiveDirection, solvers::StaticSolver, ProblemVariables, Solution, Solver,
-    SolverModel,
+    constraint, solvers::ObjectiveDirection, solvers::StaticSolver, variable, Expression,
+    ProblemVariables, Solution, Solver, SolverModel, Variable,
 };
 
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::mpsc::channel;
 use std::thread;
 
 // generic type for the Errors implemented by different solver interfaces
-type SolverError<S> = <<S as Solver>::Model as good_lp::SolverModel>::Error;
+type SolverError<S> = <<S as Solver>::Model as SolverModel>::Error;
+
+/// One `(reaction_id, new_lb, new_ub)` bound override; a [`screen`] variant is
+/// a `Vec` of these.
+type Variant = (String, f64, f64);
+
+/// Feasibility status reported by the underlying solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionStatus {
+    /// An optimal solution was found.
+    Optimal,
+    /// The solver stopped after reaching a time limit.
+    TimeLimit,
+    /// The solver stopped after reaching an optimality gap limit.
+    GapLimit,
+}
+
+impl From<good_lp::SolutionStatus> for SolutionStatus {
+    fn from(status: good_lp::SolutionStatus) -> Self {
+        match status {
+            good_lp::SolutionStatus::Optimal => SolutionStatus::Optimal,
+            good_lp::SolutionStatus::TimeLimit => SolutionStatus::TimeLimit,
+            good_lp::SolutionStatus::GapLimit => SolutionStatus::GapLimit,
+        }
+    }
+}
+
+/// Result of [`fba`]: the objective value, the per-reaction flux
+/// distribution and the solver feasibility status, bundled together so
+/// callers don't need to look up the objective by name in the flux map.
+#[derive(Debug, Clone)]
+pub struct FluxSolution {
+    /// Value of the optimized objective function.
+    pub objective_value: f64,
+    /// Id of the reaction used as the objective.
+    pub objective: String,
+    /// Feasibility status reported by the solver.
+    pub status: SolutionStatus,
+    fluxes: HashMap<String, f64>,
+}
+
+impl FluxSolution {
+    /// Per-reaction flux distribution.
+    pub fn fluxes(&self) -> &HashMap<String, f64> {
+        &self.fluxes
+    }
+}
+
+impl fmt::Display for FluxSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Objective ({}): {:.6}", self.objective, self.objective_value)?;
+        let mut fluxes: Vec<(&String, &f64)> =
+            self.fluxes.iter().filter(|(_, v)| v.abs() > 1e-9).collect();
+        fluxes.sort_by(|a, b| a.0.cmp(b.0));
+        for (id, val) in fluxes {
+            writeln!(f, "  {:<30} {:>12.6}", id, val)?;
+        }
+        Ok(())
+    }
+}
 
 /// Optimize the model according to Flux Balance Analysis (FBA).
 /// FBA: [https://pubmed.ncbi.nlm.nih.gov/20212490/](https://pubmed.ncbi.nlm.nih.gov/20212490/)
 ///
 /// # Example
 /// ```
-/// use kair::{ModelLP, fba};
+/// use kair::{ModelLp, fba};
 /// use std::{str::FromStr, convert::Into};
 /// use good_lp::default_solver;
 ///
@@ -28,21 +88,25 @@ type SolverError<S> = <<S as Solver>::Model as good_lp::SolverModel>::Error;
 /// # let mut contents = String::new();
 /// # buf_reader.read_to_string(&mut contents).unwrap();
 /// // contents is a &str containing a SBML document
-/// let mut model = ModelLP::from_str(&contents).unwrap();
-/// println!("{:?}", fba(&mut model, default_solver).unwrap())
+/// let mut model = ModelLp::from_str(&contents).unwrap();
+/// println!("{}", fba(&mut model, default_solver).unwrap())
 /// ```
-pub fn fba<S: Solver>(
-    model: &mut ModelLP,
-    solver: S,
-) -> Result<HashMap<String, f64>, SolverError<S>> {
-    _fva_step(model, solver, ObjectiveDirection::Maximisation)
+pub fn fba<S: Solver>(model: &mut ModelLp, solver: S) -> Result<FluxSolution, SolverError<S>> {
+    let (fluxes, status) = _fva_step(model, solver, ObjectiveDirection::Maximisation)?;
+    let objective_value = fluxes[&model.objective];
+    Ok(FluxSolution {
+        objective_value,
+        objective: model.objective.clone(),
+        status,
+        fluxes,
+    })
 }
 
 fn _fva_step<S: Solver>(
-    model: &mut ModelLP,
+    model: &mut ModelLp,
     solver: S,
     direction: ObjectiveDirection,
-) -> Result<HashMap<String, f64>, SolverError<S>> {
+) -> Result<(HashMap<String, f64>, SolutionStatus), SolverError<S>> {
     let mut problem = ProblemVariables::new();
     model.populate_model(&mut problem);
     let mut problem = problem
@@ -50,11 +114,138 @@ fn _fva_step<S: Solver>(
         .using(solver);
     model.add_constraints::<S>(&mut problem);
     let solution = problem.solve()?;
-    Ok(model
+    let fluxes = model
+        .variables
+        .iter()
+        .map(|(id, var)| (id.clone(), solution.value(*var)))
+        .collect();
+    Ok((fluxes, solution.status().into()))
+}
+
+/// Perform Parsimonious FBA (pFBA): fix the objective to its optimum, then
+/// minimize total flux, giving a single reproducible solution instead of one
+/// of `fba`'s many alternative optima.
+///
+/// # Example
+/// ```
+/// use kair::{ModelLp, flux_analysis::pfba};
+/// use std::str::FromStr;
+/// use good_lp::default_solver;
+///
+/// # use std::{fs::File, io::{BufReader, prelude::*}};
+///
+/// # let file = std::fs::File::open("examples/EcoliCore.xml").unwrap();
+/// # let mut buf_reader = BufReader::new(file);
+/// # let mut contents = String::new();
+/// # buf_reader.read_to_string(&mut contents).unwrap();
+/// let mut model = ModelLp::from_str(&contents).unwrap();
+/// println!("{}", pfba(&mut model, default_solver).unwrap())
+/// ```
+pub fn pfba<S>(model: &mut ModelLp, solver: S) -> Result<FluxSolution, Box<dyn std::error::Error>>
+where
+    S: StaticSolver + Clone,
+{
+    let original_solution = fba(model, solver.clone())?;
+    let fix_to = original_solution.objective_value;
+    let objective_id = model.objective.clone();
+    let objective = model.get_objective_reaction()?;
+    objective.lb = fix_to;
+    objective.ub = fix_to;
+
+    let mut problem = ProblemVariables::new();
+    model.populate_model(&mut problem);
+    let aux_vars: HashMap<String, (Variable, Variable)> = model
+        .reactions
+        .keys()
+        .map(|id| {
+            (
+                id.clone(),
+                (
+                    problem.add(variable().min(0.)),
+                    problem.add(variable().min(0.)),
+                ),
+            )
+        })
+        .collect();
+    let minimize_enzyme_usage: Expression = aux_vars
+        .values()
+        .map(|(v_fwd, v_rev)| Expression::from(*v_fwd) + Expression::from(*v_rev))
+        .sum();
+    let mut problem = problem.minimise(minimize_enzyme_usage).using(solver);
+    model.add_constraints::<S>(&mut problem);
+    for (id, var) in model.variables.iter() {
+        let (v_fwd, v_rev) = aux_vars[id];
+        problem.add_constraint(constraint::eq(
+            Expression::from(*var) - Expression::from(v_fwd) + Expression::from(v_rev),
+            0.,
+        ));
+    }
+    let solution = problem.solve()?;
+    let fluxes: HashMap<String, f64> = model
         .variables
         .iter()
         .map(|(id, var)| (id.clone(), solution.value(*var)))
-        .collect())
+        .collect();
+    let objective_value = fluxes[&objective_id];
+    Ok(FluxSolution {
+        objective_value,
+        objective: objective_id,
+        status: solution.status().into(),
+        fluxes,
+    })
+}
+
+/// Result of [`fva`]: the per-reaction `(lower, upper)` flux range, plus
+/// derived summary statistics.
+#[derive(Debug, Clone)]
+pub struct FluxVariabilitySummary {
+    ranges: HashMap<String, (f64, f64)>,
+}
+
+impl FluxVariabilitySummary {
+    /// Per-reaction `(lower, upper)` flux bounds.
+    pub fn ranges(&self) -> &HashMap<String, (f64, f64)> {
+        &self.ranges
+    }
+    /// Reactions whose flux is fixed at (approximately) zero in both
+    /// directions, i.e. they carry no flux in any optimal solution.
+    pub fn blocked_reactions(&self) -> Vec<&str> {
+        self.ranges
+            .iter()
+            .filter(|(_, (lo, hi))| lo.abs() < 1e-9 && hi.abs() < 1e-9)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+    /// Fraction of reactions whose lower and upper flux bound differ, i.e.
+    /// can take more than one value across optimal solutions.
+    pub fn variable_fraction(&self) -> f64 {
+        if self.ranges.is_empty() {
+            return 0.;
+        }
+        let variable = self
+            .ranges
+            .values()
+            .filter(|(lo, hi)| (hi - lo).abs() > 1e-9)
+            .count();
+        variable as f64 / self.ranges.len() as f64
+    }
+}
+
+impl fmt::Display for FluxVariabilitySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:.1}% of reactions variable, {} blocked",
+            self.variable_fraction() * 100.,
+            self.blocked_reactions().len()
+        )?;
+        let mut ranges: Vec<(&String, &(f64, f64))> = self.ranges.iter().collect();
+        ranges.sort_by(|a, b| a.0.cmp(b.0));
+        for (id, (lo, hi)) in ranges {
+            writeln!(f, "  {:<30} [{:>10.6}, {:>10.6}]", id, lo, hi)?;
+        }
+        Ok(())
+    }
 }
 
 /// Perform [Flux Variability Analysis](https://www.ncbi.nlm.nih.gov/pmc/articles/PMC2963619//).
@@ -65,12 +256,13 @@ fn _fva_step<S: Solver>(
 ///     2. Maximize a FBA with reaction as objective.
 /// 3. Report solution.
 ///
-/// The returned `HashMap<String, (f64, f64)` contains the reaction id as key
-/// and a tuple of the lower possible flux and the upper possible flux, respectively.
+/// The returned [`FluxVariabilitySummary`] exposes the reaction id to
+/// `(lower, upper)` flux range mapping, along with blocked reactions and the
+/// fraction of reactions that are variable.
 ///
 /// # Example
 /// ```
-/// use kair::{ModelLP, flux_analysis::fva};
+/// use kair::{ModelLp, flux_analysis::fva};
 /// use std::{str::FromStr, convert::Into};
 /// use good_lp::default_solver;
 ///
@@ -81,24 +273,24 @@ fn _fva_step<S: Solver>(
 /// # let mut contents = String::new();
 /// # buf_reader.read_to_string(&mut contents).unwrap();
 /// // contents is a &str containing a SBML document
-/// let mut model = ModelLP::from_str(&contents).unwrap();
+/// let mut model = ModelLp::from_str(&contents).unwrap();
 /// let reactions = &model.  reactions.iter().map(|(k, _v)| k.clone()).collect::<Vec<String>>();
-/// println!("Reaction  LowerFlux  UpperFlux\n{:?}", fva(
+/// println!("{}", fva(
 ///     &mut model,
 ///     default_solver,
 ///     reactions,
 /// ).unwrap())
 /// ```
 pub fn fva<S>(
-    model: &mut ModelLP,
+    model: &mut ModelLp,
     solver: S,
     reactions: &[String],
-) -> Result<HashMap<String, (f64, f64)>, Box<dyn std::error::Error>>
+) -> Result<FluxVariabilitySummary, Box<dyn std::error::Error>>
 where
     S: StaticSolver + Clone + Send + Sync,
 {
     let original_solution = fba(model, solver.clone())?;
-    let fix_to = original_solution[&model.objective];
+    let fix_to = original_solution.objective_value;
     let objective = model.get_objective_reaction()?;
     objective.lb = fix_to;
     objective.ub = fix_to;
@@ -119,13 +311,13 @@ where
             for reaction in reactions {
                 model.objective = reaction.clone();
                 let upper_value = match fba(&mut model, solver.clone()) {
-                    Ok(sol) => sol[&model.objective],
-                    _ => std::f64::NAN,
+                    Ok(sol) => sol.objective_value,
+                    _ => f64::NAN,
                 };
                 let lower_value =
                     match _fva_step(&mut model, solver.clone(), ObjectiveDirection::Minimisation) {
-                        Ok(sol) => sol[&model.objective],
-                        _ => std::f64::NAN,
+                        Ok((sol, _)) => sol[&model.objective],
+                        _ => f64::NAN,
                     };
                 tx.send((reaction.clone(), (lower_value, upper_value)))
                     .expect("Could not send data!");
@@ -137,5 +329,80 @@ where
         let (reac_id, bounds) = rx.recv()?;
         result.insert(reac_id, bounds);
     }
+    Ok(FluxVariabilitySummary { ranges: result })
+}
+
+/// Run a batch of model variants (e.g. reaction or gene knockouts) through
+/// FBA in parallel and collect their objective values.
+///
+/// Each entry of `variants` is a set of `(reaction_id, new_lb, new_ub)` bound
+/// overrides describing one variant, applied to a clone of `model` before
+/// solving; pass an empty `Vec` for the unperturbed base case. The returned
+/// `Vec<f64>` is aligned with `variants`, one objective value per entry.
+///
+/// Variants are split across threads using the same `num_cpus` + `channel` +
+/// `thread::spawn` pattern as [`fva`].
+///
+/// # Example
+/// ```
+/// use kair::{ModelLp, flux_analysis::screen};
+/// use std::str::FromStr;
+/// use good_lp::default_solver;
+///
+/// # use std::{fs::File, io::{BufReader, prelude::*}};
+///
+/// # let file = std::fs::File::open("examples/EcoliCore.xml").unwrap();
+/// # let mut buf_reader = BufReader::new(file);
+/// # let mut contents = String::new();
+/// # buf_reader.read_to_string(&mut contents).unwrap();
+/// let model = ModelLp::from_str(&contents).unwrap();
+/// let variants = vec![vec![], vec![("R_EX_o2_e".to_string(), 0., 0.)]];
+/// println!("{:?}", screen(&model, default_solver, variants).unwrap())
+/// ```
+pub fn screen<S>(
+    model: &ModelLp,
+    solver: S,
+    variants: Vec<Vec<Variant>>,
+) -> Result<Vec<f64>, Box<dyn std::error::Error>>
+where
+    S: StaticSolver + Clone + Send + Sync,
+{
+    let cpus = num_cpus::get();
+    let (tx, rx) = channel();
+    let n_variants = variants.len();
+    let variants_per_job = n_variants / cpus;
+    let variants: Vec<(usize, Vec<Variant>)> = variants.into_iter().enumerate().collect();
+
+    for i in 0..cpus {
+        let model = model.clone();
+        let tx = tx.clone();
+        let solver = solver.clone();
+        let (lower, mut upper) = (i * variants_per_job, variants_per_job * (i + 1));
+        if (cpus - 1) == i {
+            upper = n_variants
+        }
+        let variants = variants[lower..upper].to_vec();
+        thread::spawn(move || {
+            for (idx, overrides) in variants {
+                let mut variant = model.clone();
+                for (reac_id, lb, ub) in overrides {
+                    if let Some(reaction) = variant.reactions.get_mut(&reac_id) {
+                        reaction.lb = lb;
+                        reaction.ub = ub;
+                    }
+                }
+                let objective_value = match fba(&mut variant, solver.clone()) {
+                    Ok(sol) => sol.objective_value,
+                    _ => f64::NAN,
+                };
+                tx.send((idx, objective_value)).expect("Could not send data!");
+            }
+        });
+    }
+    let mut result = vec![0f64; n_variants];
+    for _ in 0..n_variants {
+        let (idx, value) = rx.recv()?;
+        result[idx] = value;
+    }
     Ok(result)
 }
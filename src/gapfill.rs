@@ -0,0 +1,80 @@
+//! MILP-based gap-filling against a universal reaction database.
+use crate::model::ModelLp;
+use good_lp::{
+    constraint, solvers::StaticSolver, variable, Expression, ProblemVariables, Solution,
+    SolverModel, Variable,
+};
+
+use std::collections::HashMap;
+
+/// Find the minimal set of reactions from `universal` that, when added to
+/// `model`, let its objective reach at least `target`. Reactions already
+/// present in `model` are left alone, so only genuinely new additions are
+/// toggled by the MILP's binary indicators.
+///
+/// # Example
+/// ```
+/// use kair::{ModelLp, gapfill::gapfill};
+/// use std::str::FromStr;
+/// use good_lp::default_solver;
+///
+/// # use std::{fs::File, io::{BufReader, prelude::*}};
+///
+/// # let file = std::fs::File::open("examples/EcoliCore.xml").unwrap();
+/// # let mut buf_reader = BufReader::new(file);
+/// # let mut contents = String::new();
+/// # buf_reader.read_to_string(&mut contents).unwrap();
+/// let model = ModelLp::from_str(&contents).unwrap();
+/// let universal = model.clone();
+/// println!("{:?}", gapfill(&model, &universal, 0.1, default_solver).unwrap())
+/// ```
+pub fn gapfill<S: StaticSolver>(
+    model: &ModelLp,
+    universal: &ModelLp,
+    target: f64,
+    solver: S,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut combined = model.clone();
+    for (id, reaction) in universal.reactions.iter() {
+        if !model.reactions.contains_key(id) {
+            combined.reactions.insert(id.clone(), reaction.clone());
+        }
+    }
+
+    let mut problem = ProblemVariables::new();
+    combined.populate_model(&mut problem);
+
+    let indicators: HashMap<String, Variable> = universal
+        .reactions
+        .keys()
+        .filter(|id| !model.reactions.contains_key(*id))
+        .map(|id| (id.clone(), problem.add(variable().binary())))
+        .collect();
+
+    let minimize_additions: Expression = indicators.values().map(|y| Expression::from(*y)).sum();
+    let mut problem = problem.minimise(minimize_additions).using(solver);
+    combined.add_constraints::<S>(&mut problem);
+    problem.add_constraint(constraint::geq(
+        Expression::from(combined.get_objective()),
+        target,
+    ));
+    for (id, y) in indicators.iter() {
+        let v = combined.variables[id];
+        let reaction = &universal.reactions[id];
+        problem.add_constraint(constraint::geq(
+            Expression::from(v) - Expression::from(*y) * reaction.lb,
+            0.,
+        ));
+        problem.add_constraint(constraint::leq(
+            Expression::from(v) - Expression::from(*y) * reaction.ub,
+            0.,
+        ));
+    }
+
+    let solution = problem.solve()?;
+    Ok(indicators
+        .into_iter()
+        .filter(|(_, y)| solution.value(*y) > 0.5)
+        .map(|(id, _)| id)
+        .collect())
+}
@@ -30,7 +30,7 @@
 //! let mut contents = String::new();
 //! buf_reader.read_to_string(&mut contents).unwrap();
 //! let mut model = ModelLp::from_str(&contents).unwrap();
-//! for (name, val) in fba(&mut model, default_solver).unwrap().iter() {
+//! for (name, val) in fba(&mut model, default_solver).unwrap().fluxes().iter() {
 //!     println!("{} = {}", name, val)
 //! }
 //! ```
@@ -41,6 +41,7 @@
 //! * [rust_sbml](https://docs.rs/rust_sbml/0.3.0/rust_sbml/): SBML parser in rust.
 //! * [cobrapy](https://github.com/opencobra/cobrapy/): fully featured COBRA package written in Python.
 pub mod flux_analysis;
+pub mod gapfill;
 pub mod model;
 
 pub use flux_analysis::fba;
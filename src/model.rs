@@ -1,9 +1,10 @@
 //! Structs for the formulation of the LP problem from the SBML model
 use custom_error::custom_error;
 use good_lp::{constraint, variable, Expression, ProblemVariables, Solver, SolverModel, Variable};
-use rust_sbml::{Model, Parameter, Reaction, Species, SpeciesReference};
+use rust_sbml::{Model, Parameter, Reaction, SpeciesReference};
+use serde::Deserialize;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 custom_error! {
@@ -35,7 +36,7 @@ pub struct ModelLp {
     /// Name from SBML document
     pub name: String,
     /// Metabolites from the SBML document
-    pub metabolites: HashMap<String, Species>,
+    pub metabolites: HashMap<String, Metabolite>,
     /// Reactions from the SBML document
     pub reactions: HashMap<String, ReactionLp>,
     /// Parsed from reactions, variables of LP problem
@@ -46,6 +47,24 @@ pub struct ModelLp {
     pub objective: String,
     /// Parsed stoichiometry matrix
     pub stoichiometry: HashMap<String, Vec<Expression>>,
+    /// Generalized coupling constraints: each entry is a linear combination
+    /// of reaction fluxes `Σ cᵢ·vᵢ` (keyed by reaction id) bounded by
+    /// `[cl, cu]`. Use [`add_coupling`](ModelLp::add_coupling) to register
+    /// one. Supports loopless-style and thermodynamic coupling, community
+    /// model abundance coupling, and ratio constraints between fluxes that
+    /// can't be expressed as simple reaction bounds.
+    pub coupling: Vec<(HashMap<String, f64>, f64, f64)>,
+}
+
+/// Metabolite translated from a SBML `Specie` (or a BiGG JSON metabolite
+/// entry) for ease of use, keeping only the fields `rust_sbml` exposes
+/// publicly so it can be built directly by [`ModelLp::from_json`] too.
+#[derive(Clone)]
+pub struct Metabolite {
+    /// Id of the metabolite
+    pub id: String,
+    /// Compartment the metabolite belongs to
+    pub compartment: String,
 }
 
 /// Reaction struct translated from a SBML Reaction for ease of use.
@@ -55,25 +74,134 @@ pub struct ReactionLp {
     pub lb: f64,
     /// upper bound of the reaction
     pub ub: f64,
-    id: String,
+    /// Gene-protein-reaction association in disjunctive normal form: the
+    /// outer `Vec` is OR-connected, each inner `Vec` of gene ids is
+    /// AND-connected, e.g. `(geneA and geneB) or geneC`. Only populated by
+    /// [`ModelLp::from_json`]; `rust_sbml` doesn't expose the fbc GPR
+    /// association, so SBML-imported reactions get an empty `gpr`.
+    pub gpr: Vec<Vec<String>>,
     reactants: Vec<SpeciesReference>,
     products: Vec<SpeciesReference>,
 }
 
+/// A parsed gene-protein-reaction boolean expression, prior to DNF expansion.
+enum GprExpr {
+    Gene(String),
+    And(Box<GprExpr>, Box<GprExpr>),
+    Or(Box<GprExpr>, Box<GprExpr>),
+}
+
+/// Recursive-descent parser for GPR rules, respecting parentheses and
+/// `and`/`or` precedence (`and` binds tighter than `or`).
+struct GprParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> GprParser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn advance(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Option<GprExpr> {
+        let mut expr = self.parse_and()?;
+        while self.peek().is_some_and(|t| t.eq_ignore_ascii_case("or")) {
+            self.advance();
+            expr = GprExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Some(expr)
+    }
+
+    fn parse_and(&mut self) -> Option<GprExpr> {
+        let mut expr = self.parse_atom()?;
+        while self.peek().is_some_and(|t| t.eq_ignore_ascii_case("and")) {
+            self.advance();
+            expr = GprExpr::And(Box::new(expr), Box::new(self.parse_atom()?));
+        }
+        Some(expr)
+    }
+
+    fn parse_atom(&mut self) -> Option<GprExpr> {
+        match self.advance()? {
+            "(" => {
+                let expr = self.parse_or()?;
+                if self.peek() == Some(")") {
+                    self.advance();
+                }
+                Some(expr)
+            }
+            gene => Some(GprExpr::Gene(gene.to_owned())),
+        }
+    }
+}
+
+/// Expand a parsed GPR expression into disjunctive normal form, distributing
+/// `and` over `or` so that a gene required by every disjunct (e.g. via a
+/// nested `a and (b or c)` grouping) appears in every resulting conjunction.
+fn gpr_to_dnf(expr: &GprExpr) -> Vec<Vec<String>> {
+    match expr {
+        GprExpr::Gene(gene) => vec![vec![gene.clone()]],
+        GprExpr::Or(lhs, rhs) => {
+            let mut dnf = gpr_to_dnf(lhs);
+            dnf.extend(gpr_to_dnf(rhs));
+            dnf
+        }
+        GprExpr::And(lhs, rhs) => {
+            let lhs_dnf = gpr_to_dnf(lhs);
+            let rhs_dnf = gpr_to_dnf(rhs);
+            lhs_dnf
+                .iter()
+                .flat_map(|lhs_conjunction| {
+                    rhs_dnf.iter().map(move |rhs_conjunction| {
+                        let mut merged = lhs_conjunction.clone();
+                        for gene in rhs_conjunction {
+                            if !merged.contains(gene) {
+                                merged.push(gene.clone());
+                            }
+                        }
+                        merged
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// Parse a gene-protein-reaction rule (BiGG JSON's `gene_reaction_rule`,
+/// see [`ModelLp::from_json`]) into disjunctive normal form: an outer,
+/// OR-connected `Vec` of inner, AND-connected conjunctions of gene ids.
+/// Parentheses are honored, so a nested grouping like `"a and (b or c)"`
+/// correctly expands to `[[a, b], [a, c]]` rather than dropping `a` from one
+/// of the disjuncts.
+fn parse_gpr(rule: &str) -> Vec<Vec<String>> {
+    let tokens: Vec<String> = rule
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect();
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+    let mut parser = GprParser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    parser.parse_or().map(|expr| gpr_to_dnf(&expr)).unwrap_or_default()
+}
+
 impl ReactionLp {
     fn from_reaction(
         reaction: Reaction,
         parameters: &HashMap<String, Parameter>,
     ) -> Result<ReactionLp, SBMLError> {
         Ok(ReactionLp {
-            id: format!(
-                "{}_{}",
-                reaction.id,
-                match reaction.compartment.as_ref() {
-                    Some(s) => s.to_owned(),
-                    _ => String::from(""),
-                }
-            ),
             lb: match reaction.lower_bound.as_ref() {
                 // a parameter in reaction is guaranteed to be on the list of parameters of SBML
                 Some(s) => parameters
@@ -110,8 +238,11 @@ impl ReactionLp {
                     _ => 1000.,
                 },
             },
-            reactants: reaction.list_of_reactants.species_references,
-            products: reaction.list_of_products.species_references,
+            reactants: reaction.list_of_reactants.0,
+            products: reaction.list_of_products.0,
+            // rust_sbml doesn't expose fbc:geneProductAssociation, so GPRs are
+            // only available through the BiGG JSON path (see `from_json`).
+            gpr: Vec::new(),
         })
     }
 }
@@ -136,6 +267,94 @@ impl ModelLp {
         Self::from(input_sbml)
     }
 
+    /// Build a [`ModelLp`] from a BiGG-style JSON export instead of SBML.
+    ///
+    /// Accepts the schema used by BiGG's JSON download mirrors: top-level
+    /// `reactions` (with `metabolites`, `lower_bound`/`upper_bound`,
+    /// `gene_reaction_rule` and `objective_coefficient`) and `metabolites`
+    /// arrays. The reaction with a nonzero `objective_coefficient` is taken
+    /// as the model objective.
+    ///
+    /// # Example
+    /// ```
+    /// use kair::ModelLp;
+    ///
+    /// let contents = r#"{
+    ///     "id": "toy",
+    ///     "reactions": [{
+    ///         "id": "R1",
+    ///         "metabolites": {"A": -1.0, "B": 1.0},
+    ///         "lower_bound": 0.0,
+    ///         "upper_bound": 1000.0,
+    ///         "objective_coefficient": 1.0
+    ///     }],
+    ///     "metabolites": [{"id": "A"}, {"id": "B"}]
+    /// }"#;
+    /// ModelLp::from_json(contents).unwrap();
+    /// ```
+    pub fn from_json(input_json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let json_model: JsonModel = serde_json::from_str(input_json)?;
+        let mut objective = String::new();
+        let mut reactions = HashMap::new();
+        for reac in json_model.reactions.into_iter() {
+            if reac.objective_coefficient != 0. {
+                objective = reac.id.clone();
+            }
+            let mut reactants = Vec::new();
+            let mut products = Vec::new();
+            for (met_id, coeff) in reac.metabolites.into_iter() {
+                let sref = SpeciesReference {
+                    species: met_id,
+                    constant: true,
+                    sbo_term: None,
+                    id: None,
+                    name: None,
+                    stoichiometry: Some(coeff.abs()),
+                };
+                if coeff < 0. {
+                    reactants.push(sref);
+                } else {
+                    products.push(sref);
+                }
+            }
+            reactions.insert(
+                reac.id.clone(),
+                ReactionLp {
+                    lb: reac.lower_bound,
+                    ub: reac.upper_bound,
+                    reactants,
+                    products,
+                    gpr: parse_gpr(&reac.gene_reaction_rule),
+                },
+            );
+        }
+        let metabolites = json_model
+            .metabolites
+            .into_iter()
+            .map(|met| {
+                (
+                    met.id.clone(),
+                    Metabolite {
+                        id: met.id,
+                        compartment: String::new(),
+                    },
+                )
+            })
+            .collect();
+
+        Ok(ModelLp {
+            id: json_model.id.unwrap_or_default(),
+            name: json_model.name.unwrap_or_default(),
+            metabolites,
+            reactions,
+            variables: HashMap::new(),
+            config: HashMap::new(),
+            objective,
+            stoichiometry: HashMap::new(),
+            coupling: Vec::new(),
+        })
+    }
+
     fn reac_expr(&self, met: &SpeciesReference, reac: &str, com: f64) -> Expression {
         Expression::from(self.variables[reac].to_owned())
             * match met.stoichiometry {
@@ -152,13 +371,13 @@ impl ModelLp {
             reaction.reactants.iter().for_each(|sref| {
                 let cons = &mut stoichiometry
                     .entry(sref.species.to_owned())
-                    .or_insert_with(Vec::new);
+                    .or_default();
                 cons.push(self.reac_expr(sref, reac_id, -1.))
             });
             reaction.products.iter().for_each(|sref| {
                 let cons = &mut stoichiometry
                     .entry(sref.species.to_owned())
-                    .or_insert_with(Vec::new);
+                    .or_default();
                 cons.push(self.reac_expr(sref, reac_id, 1.));
             });
         }
@@ -176,11 +395,48 @@ impl ModelLp {
                 obj: self.objective.to_owned(),
             })
     }
-    /// Add the constraints to th problem
+    /// Knock out a set of genes.
+    ///
+    /// For each reaction, any GPR conjunction (AND-group) that contains a
+    /// deleted gene is dropped; if no conjunction survives, the GPR can no
+    /// longer be satisfied and the reaction is blocked by setting
+    /// `lb = ub = 0.0`. Reactions without a GPR (e.g. exchanges, spontaneous
+    /// reactions) are left untouched. Combined with [`flux_analysis::screen`](crate::flux_analysis::screen),
+    /// this enables single- and double-gene-knockout essentiality studies.
+    pub fn delete_genes(&mut self, genes: &HashSet<String>) {
+        for reaction in self.reactions.values_mut() {
+            if reaction.gpr.is_empty() {
+                continue;
+            }
+            reaction
+                .gpr
+                .retain(|conjunction| !conjunction.iter().any(|gene| genes.contains(gene)));
+            if reaction.gpr.is_empty() {
+                reaction.lb = 0.0;
+                reaction.ub = 0.0;
+            }
+        }
+    }
+    /// Register a coupling constraint `cl <= Σ cᵢ·vᵢ <= cu` over a linear
+    /// combination of reaction fluxes, e.g. a fixed ratio between two
+    /// exchange reactions or a thermodynamic/abundance coupling row.
+    pub fn add_coupling(&mut self, coeffs: HashMap<String, f64>, cl: f64, cu: f64) {
+        self.coupling.push((coeffs, cl, cu));
+    }
+    /// Add the constraints to th problem: the steady-state mass-balance
+    /// equalities `S·v = 0` plus any registered [`coupling`](ModelLp::coupling) rows.
     pub fn add_constraints<S: Solver>(&self, model: &mut S::Model) {
         for (_, cons) in self.stoichiometry.iter() {
             model.add_constraint(constraint::eq(cons.iter().sum::<Expression>(), 0.));
         }
+        for (coeffs, cl, cu) in self.coupling.iter() {
+            let expr: Expression = coeffs
+                .iter()
+                .map(|(reac_id, coeff)| Expression::from(self.variables[reac_id]) * *coeff)
+                .sum();
+            model.add_constraint(constraint::geq(expr.clone(), *cl));
+            model.add_constraint(constraint::leq(expr, *cu));
+        }
     }
     fn add_vars(&mut self, problem: &mut ProblemVariables) {
         self.variables = self
@@ -206,10 +462,22 @@ impl FromStr for ModelLp {
 
 impl From<Model> for ModelLp {
     fn from(mut model: Model) -> ModelLp {
-        let metabolites = model.species;
+        let metabolites = model
+            .species
+            .into_iter()
+            .map(|(id, specie)| {
+                (
+                    id,
+                    Metabolite {
+                        id: specie.id,
+                        compartment: specie.compartment,
+                    },
+                )
+            })
+            .collect();
         let config = model.parameters;
         let mut reactions = HashMap::new();
-        let reac_ids: Vec<String> = model.reactions.iter().map(|(k, _)| k.to_owned()).collect();
+        let reac_ids: Vec<String> = model.reactions.keys().map(|k| k.to_owned()).collect();
         for key in reac_ids.iter() {
             reactions.insert(
                 key.to_owned(),
@@ -217,15 +485,9 @@ impl From<Model> for ModelLp {
                 ReactionLp::from_reaction(model.reactions.remove(key).unwrap(), &config).unwrap(),
             );
         }
-        let objective = model.objectives.unwrap()[0].to_owned();
-        let id = match model.id {
-            Some(s) => s,
-            _ => "".to_string(),
-        };
-        let name = match model.name {
-            Some(s) => s,
-            _ => "".to_string(),
-        };
+        let objective = model.objectives[0].to_owned();
+        let id = model.annotation.id.unwrap_or_default();
+        let name = model.annotation.name.unwrap_or_default();
 
         ModelLp {
             id,
@@ -236,6 +498,76 @@ impl From<Model> for ModelLp {
             config,
             objective,
             stoichiometry: HashMap::<_, _>::new(),
+            coupling: Vec::new(),
+        }
+    }
+}
+
+/// BiGG-style JSON model document, see [`ModelLp::from_json`].
+#[derive(Deserialize)]
+struct JsonModel {
+    id: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    reactions: Vec<JsonReaction>,
+    #[serde(default)]
+    metabolites: Vec<JsonMetabolite>,
+}
+
+/// BiGG-style JSON reaction entry, see [`ModelLp::from_json`].
+#[derive(Deserialize)]
+struct JsonReaction {
+    id: String,
+    metabolites: HashMap<String, f64>,
+    lower_bound: f64,
+    upper_bound: f64,
+    #[serde(default)]
+    gene_reaction_rule: String,
+    #[serde(default)]
+    objective_coefficient: f64,
+}
+
+/// BiGG-style JSON metabolite entry, see [`ModelLp::from_json`].
+#[derive(Deserialize)]
+struct JsonMetabolite {
+    id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut dnf: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        for conjunction in dnf.iter_mut() {
+            conjunction.sort();
         }
+        dnf.sort();
+        dnf
+    }
+
+    #[test]
+    fn parse_gpr_distributes_and_over_nested_or() {
+        // isozyme complex: b0001 is required alongside *either* b0002 or b0003,
+        // so it must appear in both resulting conjunctions.
+        let dnf = sorted(parse_gpr("b0001 and (b0002 or b0003)"));
+        assert_eq!(
+            dnf,
+            sorted(vec![
+                vec!["b0001".to_string(), "b0002".to_string()],
+                vec!["b0001".to_string(), "b0003".to_string()],
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_gpr_flat_rule_unchanged() {
+        let dnf = sorted(parse_gpr("geneA and geneB or geneC"));
+        assert_eq!(
+            dnf,
+            sorted(vec![
+                vec!["geneA".to_string(), "geneB".to_string()],
+                vec!["geneC".to_string()],
+            ])
+        );
     }
 }